@@ -0,0 +1,50 @@
+use super::bindings::{
+    CKR_ARGUMENTS_BAD, CKR_OK, CK_FLAGS, CK_NOTIFY, CK_RV, CK_SESSION_HANDLE, CK_SESSION_HANDLE_PTR,
+    CK_SLOT_ID, CK_VOID_PTR,
+};
+use crate::state::ManagerProxy;
+
+/// Opens a session between an application and a token in a particular slot
+///
+/// # Arguments
+///
+/// * `slotID` - the slot to open the session against
+/// * `flags` - indicates the type of session being opened
+/// * `pApplication` - an application-defined pointer passed to the notification callback
+/// * `Notify` - the notification callback
+/// * `phSession` - points to the location that receives the new session's handle
+#[allow(non_snake_case)]
+pub(crate) fn C_OpenSession(
+    slotID: CK_SLOT_ID,
+    _flags: CK_FLAGS,
+    _pApplication: CK_VOID_PTR,
+    _Notify: CK_NOTIFY,
+    phSession: CK_SESSION_HANDLE_PTR,
+) -> CK_RV {
+    if phSession.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+
+    let manager_proxy = ManagerProxy::new();
+    let session = match manager_proxy.open_session(slotID) {
+        Ok(session) => session,
+        Err(rv) => return rv,
+    };
+
+    unsafe {
+        *phSession = session;
+    }
+    CKR_OK as CK_RV
+}
+
+/// Closes a session between an application and a token
+///
+/// # Arguments
+///
+/// * `hSession` - the session's handle
+#[allow(non_snake_case)]
+pub(crate) fn C_CloseSession(hSession: CK_SESSION_HANDLE) -> CK_RV {
+    let manager_proxy = ManagerProxy::new();
+    manager_proxy.unbind_session(&hSession);
+    CKR_OK as CK_RV
+}
@@ -6,9 +6,9 @@ use super::{
         CKR_ARGUMENTS_BAD, CKR_HOST_MEMORY, CKR_OK, CK_FUNCTION_LIST, CK_FUNCTION_LIST_PTR_PTR,
         CK_INFO, CK_INFO_PTR, CK_RV, CK_VERSION, CK_VOID_PTR,
     },
-    unsupported,
+    slots, unsupported,
 };
-use crate::state::StateAccessor;
+use crate::state::{backend, ManagerProxy};
 
 /// Initializes the Cryptoki library
 ///
@@ -19,8 +19,8 @@ use crate::state::StateAccessor;
 pub(crate) fn C_Initialize(pInitArgs: CK_VOID_PTR) -> CK_RV {
     // TODO: check later if some actions are required
 
-    let state_accessor = StateAccessor::new();
-    if let Err(err) = state_accessor.initialize_state() {
+    let manager_proxy = ManagerProxy::new();
+    if let Err(err) = manager_proxy.initialize_state(backend::default_backend()) {
         return err.into_ck_rv();
     }
     CKR_OK as CK_RV
@@ -37,8 +37,8 @@ pub(crate) fn C_Finalize(pReserved: CK_VOID_PTR) -> CK_RV {
     if !pReserved.is_null() {
         return CKR_ARGUMENTS_BAD as CK_RV;
     }
-    let state_accessor = StateAccessor::new();
-    if let Err(err) = state_accessor.finalize() {
+    let manager_proxy = ManagerProxy::new();
+    if let Err(err) = manager_proxy.finalize() {
         return err.into_ck_rv();
     }
 
@@ -81,11 +81,11 @@ pub(super) fn C_GetFunctionList(ppFunctionList: CK_FUNCTION_LIST_PTR_PTR) -> CK_
         C_Finalize: Some(api::C_Finalize),
         C_GetInfo: Some(api::C_GetInfo),
         C_GetFunctionList: Some(api::C_GetFunctionList),
-        C_GetSlotList: Some(api::C_GetSlotList),
-        C_GetSlotInfo: Some(api::C_GetSlotInfo),
-        C_GetTokenInfo: Some(api::C_GetTokenInfo),
-        C_GetMechanismList: Some(unsupported::C_GetMechanismList),
-        C_GetMechanismInfo: Some(unsupported::C_GetMechanismInfo),
+        C_GetSlotList: Some(slots::C_GetSlotList),
+        C_GetSlotInfo: Some(slots::C_GetSlotInfo),
+        C_GetTokenInfo: Some(slots::C_GetTokenInfo),
+        C_GetMechanismList: Some(api::C_GetMechanismList),
+        C_GetMechanismInfo: Some(api::C_GetMechanismInfo),
         C_InitToken: Some(unsupported::C_InitToken),
         C_InitPIN: Some(unsupported::C_InitPIN),
         C_SetPIN: Some(unsupported::C_SetPIN),
@@ -112,8 +112,8 @@ pub(super) fn C_GetFunctionList(ppFunctionList: CK_FUNCTION_LIST_PTR_PTR) -> CK_
         C_EncryptFinal: Some(api::C_EncryptFinal),
         C_DecryptInit: Some(api::C_DecryptInit),
         C_Decrypt: Some(api::C_Decrypt),
-        C_DecryptUpdate: Some(unsupported::C_DecryptUpdate),
-        C_DecryptFinal: Some(unsupported::C_DecryptFinal),
+        C_DecryptUpdate: Some(api::C_DecryptUpdate),
+        C_DecryptFinal: Some(api::C_DecryptFinal),
         C_DigestInit: Some(api::C_DigestInit),
         C_Digest: Some(api::C_Digest),
         C_DigestUpdate: Some(unsupported::C_DigestUpdate),
@@ -0,0 +1,171 @@
+use aes::Aes128;
+use cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+
+use crate::cryptoki::bindings::{
+    CKR_ENCRYPTED_DATA_INVALID, CKR_WRAPPED_KEY_INVALID, CKR_WRAPPING_KEY_SIZE_RANGE, CK_RV,
+};
+
+/// The standard RFC 3394 initial value, `A6A6A6A6A6A6A6A6`, checked on unwrap to detect a wrong
+/// KEK or corrupted wrapped key.
+const DEFAULT_IV: u64 = 0xA6A6A6A6A6A6A6A6;
+
+pub(crate) enum KeyWrapError {
+    /// The plaintext/wrapped key length did not satisfy RFC 3394 (a multiple of 8 bytes, at least
+    /// two 64-bit blocks of key data, i.e. 16 bytes to wrap or 24 bytes to unwrap).
+    InvalidLength,
+    /// The recovered `A` block did not match [`DEFAULT_IV`] after unwrapping.
+    IntegrityCheckFailed,
+    /// The KEK was not exactly 16 bytes, i.e. not a valid AES-128 key.
+    InvalidKekLength,
+}
+
+impl KeyWrapError {
+    pub(crate) fn into_ck_rv(self) -> CK_RV {
+        match self {
+            KeyWrapError::InvalidLength => CKR_ENCRYPTED_DATA_INVALID as CK_RV,
+            KeyWrapError::IntegrityCheckFailed => CKR_WRAPPED_KEY_INVALID as CK_RV,
+            KeyWrapError::InvalidKekLength => CKR_WRAPPING_KEY_SIZE_RANGE as CK_RV,
+        }
+    }
+}
+
+const AES_128_KEY_SIZE: usize = 16;
+
+fn to_blocks(data: &[u8]) -> Vec<[u8; 8]> {
+    data.chunks_exact(8)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect()
+}
+
+/// Wraps `plaintext` under `kek` per RFC 3394 ("AES Key Wrap"), used for `CKM_AES_KEY_WRAP`.
+///
+/// `plaintext` must be a multiple of 8 bytes and at least 16 bytes (`n >= 2` 64-bit blocks); the
+/// output is 8 bytes longer than `plaintext`.
+pub(crate) fn aes_key_wrap(kek: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    if plaintext.len() % 8 != 0 || plaintext.len() < 16 {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    if kek.len() != AES_128_KEY_SIZE {
+        return Err(KeyWrapError::InvalidKekLength);
+    }
+    let n = plaintext.len() / 8;
+    let cipher = Aes128::new(GenericArray::from_slice(kek));
+
+    let mut a = DEFAULT_IV;
+    let mut r = to_blocks(plaintext);
+    for j in 0..6u64 {
+        for (i, block) in r.iter_mut().enumerate() {
+            let i = i as u64 + 1;
+            let mut buf = [0u8; 16];
+            buf[..8].copy_from_slice(&a.to_be_bytes());
+            buf[8..].copy_from_slice(block);
+            let mut buf = GenericArray::clone_from_slice(&buf);
+            cipher.encrypt_block(&mut buf);
+
+            a = u64::from_be_bytes(buf[..8].try_into().unwrap()) ^ (n as u64 * j + i);
+            block.copy_from_slice(&buf[8..]);
+        }
+    }
+
+    let mut output = Vec::with_capacity(plaintext.len() + 8);
+    output.extend_from_slice(&a.to_be_bytes());
+    r.iter().for_each(|block| output.extend_from_slice(block));
+    Ok(output)
+}
+
+/// Inverts [`aes_key_wrap`], used for `CKM_AES_KEY_WRAP`.
+///
+/// `wrapped` must be a multiple of 8 bytes and at least 24 bytes long. Returns
+/// [`KeyWrapError::IntegrityCheckFailed`] if the recovered value does not match the RFC 3394
+/// initial value, which signals a wrong KEK or corrupted input.
+pub(crate) fn aes_key_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    if wrapped.len() % 8 != 0 || wrapped.len() < 24 {
+        return Err(KeyWrapError::InvalidLength);
+    }
+    if kek.len() != AES_128_KEY_SIZE {
+        return Err(KeyWrapError::InvalidKekLength);
+    }
+    let n = wrapped.len() / 8 - 1;
+    let cipher = Aes128::new(GenericArray::from_slice(kek));
+
+    let mut a: u64 = u64::from_be_bytes(wrapped[..8].try_into().unwrap());
+    let mut r = to_blocks(&wrapped[8..]);
+    for j in (0..6u64).rev() {
+        for (i, block) in r.iter_mut().enumerate().rev() {
+            let i = i as u64 + 1;
+            let mut buf = [0u8; 16];
+            buf[..8].copy_from_slice(&(a ^ (n as u64 * j + i)).to_be_bytes());
+            buf[8..].copy_from_slice(block);
+            let mut buf = GenericArray::clone_from_slice(&buf);
+            cipher.decrypt_block(&mut buf);
+
+            a = u64::from_be_bytes(buf[..8].try_into().unwrap());
+            block.copy_from_slice(&buf[8..]);
+        }
+    }
+
+    if a != DEFAULT_IV {
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+
+    let mut output = Vec::with_capacity(n * 8);
+    r.iter().for_each(|block| output.extend_from_slice(block));
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 3394 4.1 test vector: wrap a 128-bit key under a 128-bit KEK.
+    const KEK: [u8; 16] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+    const PLAINTEXT: [u8; 16] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE,
+        0xFF,
+    ];
+    const WRAPPED: [u8; 24] = [
+        0x1F, 0xA6, 0x8B, 0x0A, 0x81, 0x12, 0xB4, 0x47, 0xAE, 0xF3, 0x4B, 0xD8, 0xFB, 0x5A, 0x7B,
+        0x82, 0x9D, 0x3E, 0x86, 0x23, 0x71, 0xD2, 0xCF, 0xE5,
+    ];
+
+    #[test]
+    fn aes_key_wrap_matches_rfc_3394_test_vector() {
+        assert_eq!(aes_key_wrap(&KEK, &PLAINTEXT).unwrap(), WRAPPED);
+    }
+
+    #[test]
+    fn aes_key_unwrap_matches_rfc_3394_test_vector() {
+        assert_eq!(aes_key_unwrap(&KEK, &WRAPPED).unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn aes_key_unwrap_rejects_tampered_input() {
+        let mut tampered = WRAPPED;
+        tampered[0] ^= 0xFF;
+        assert!(matches!(
+            aes_key_unwrap(&KEK, &tampered),
+            Err(KeyWrapError::IntegrityCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn aes_key_wrap_rejects_non_16_byte_kek_instead_of_panicking() {
+        let short_kek = [0u8; 10];
+        assert!(matches!(
+            aes_key_wrap(&short_kek, &PLAINTEXT),
+            Err(KeyWrapError::InvalidKekLength)
+        ));
+    }
+
+    #[test]
+    fn aes_key_unwrap_rejects_non_16_byte_kek_instead_of_panicking() {
+        let short_kek = [0u8; 10];
+        assert!(matches!(
+            aes_key_unwrap(&short_kek, &WRAPPED),
+            Err(KeyWrapError::InvalidKekLength)
+        ));
+    }
+}
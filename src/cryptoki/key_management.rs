@@ -4,11 +4,16 @@ use rand::{rngs::OsRng, Rng};
 
 use super::{
     bindings::{
-        CKM_AES_KEY_GEN, CKR_ARGUMENTS_BAD, CKR_FUNCTION_NOT_SUPPORTED, CKR_OK, CK_ATTRIBUTE_PTR,
-        CK_BYTE_PTR, CK_MECHANISM_PTR, CK_OBJECT_HANDLE, CK_OBJECT_HANDLE_PTR, CK_RV,
-        CK_SESSION_HANDLE, CK_ULONG, CK_ULONG_PTR,
+        CKK_AES, CKM_AES_KEY_GEN, CKM_AES_KEY_WRAP, CKM_VENDOR_DEFINED, CKO_PRIVATE_KEY,
+        CKO_SECRET_KEY, CKR_ARGUMENTS_BAD, CKR_FUNCTION_NOT_SUPPORTED, CKR_MECHANISM_INVALID,
+        CKR_OK, CKR_TEMPLATE_INCONSISTENT, CK_ATTRIBUTE_PTR, CK_BYTE_PTR, CK_MECHANISM_PTR,
+        CK_MECHANISM_TYPE, CK_OBJECT_HANDLE, CK_OBJECT_HANDLE_PTR, CK_RV, CK_SESSION_HANDLE,
+        CK_ULONG, CK_ULONG_PTR,
+    },
+    internals::{
+        encryption::{decrypt, destructure_iv_ciphertext, encrypt},
+        key_wrap::{aes_key_unwrap, aes_key_wrap},
     },
-    internals::encryption::{decrypt, destructure_iv_ciphertext, encrypt},
     utils::FromPointer,
 };
 use crate::state::{
@@ -16,7 +21,7 @@ use crate::state::{
         cryptoki_object::CryptokiObject, private_key_object::PrivateKeyObject,
         secret_key_object::SecretKeyObject, template::Template,
     },
-    StateAccessor,
+    ManagerProxy,
 };
 
 pub(crate) type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
@@ -24,6 +29,12 @@ pub(crate) type Aes128CbcDec = cbc::Decryptor<aes::Aes128>;
 pub(crate) const AES_BLOCK_SIZE: usize = 16;
 pub(crate) const AES_IV_SIZE: usize = AES_BLOCK_SIZE;
 
+/// Vendor-specific mechanism kept around for backward compatibility with wrapped keys produced
+/// before `CKM_AES_KEY_WRAP` (RFC 3394) support was added: AES-CBC with a random IV prepended to
+/// the ciphertext, as `C_WrapKey`/`C_UnwrapKey` used to do unconditionally.
+pub(crate) const CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP: CK_MECHANISM_TYPE =
+    (CKM_VENDOR_DEFINED + 1) as CK_MECHANISM_TYPE;
+
 /// Generates a secret key or set of domain parameters, creating a new object
 ///
 /// # Arguments
@@ -57,8 +68,8 @@ pub(crate) fn C_GenerateKey(
     let key: [u8; 16] = OsRng.gen();
     object.store_value(key.into());
 
-    let state_accessor = StateAccessor::new();
-    let object_handle = match state_accessor.create_object(&hSession, Arc::new(object)) {
+    let manager_proxy = ManagerProxy::new();
+    let object_handle = match manager_proxy.create_object(&hSession, Arc::new(object)) {
         Ok(handle) => handle,
         Err(err) => err.into_ck_rv(),
     };
@@ -90,8 +101,8 @@ pub(crate) fn C_GenerateKeyPair(
     phPublicKey: CK_OBJECT_HANDLE_PTR,
     phPrivateKey: CK_OBJECT_HANDLE_PTR,
 ) -> CK_RV {
-    let state_accessor = StateAccessor::new();
-    let (private_key_handle, pubkey_handle) = match state_accessor.get_keypair(&hSession) {
+    let manager_proxy = ManagerProxy::new();
+    let (private_key_handle, pubkey_handle) = match manager_proxy.get_keypair(&hSession) {
         Ok(val) => val,
         Err(err) => return err.into_ck_rv(),
     };
@@ -123,25 +134,36 @@ pub(crate) fn C_WrapKey(
     pWrappedKey: CK_BYTE_PTR,
     pulWrappedKeyLen: CK_ULONG_PTR,
 ) -> CK_RV {
-    if pulWrappedKeyLen.is_null() {
+    if pMechanism.is_null() || pulWrappedKeyLen.is_null() {
         return CKR_ARGUMENTS_BAD as CK_RV;
     }
-    let state_accessor = StateAccessor::new();
-    let wrapping_key = match state_accessor.get_object(&hSession, &hWrappingKey) {
+    let mechanism = unsafe { *pMechanism }.mechanism;
+
+    let manager_proxy = ManagerProxy::new();
+    let wrapping_key = match manager_proxy.get_object(&hSession, &hWrappingKey) {
         Ok(val) => val,
         Err(err) => return err.into_ck_rv(),
     };
-    let private_key = match state_accessor.get_object(&hSession, &hKey) {
+    let private_key = match manager_proxy.get_object(&hSession, &hKey) {
         Ok(val) => val,
         Err(err) => return err.into_ck_rv(),
     };
     let private_key = private_key.get_value().unwrap();
-    let key = &wrapping_key.get_value().unwrap();
+    let key = wrapping_key.get_value().unwrap();
+
+    let wrapped = if mechanism as u32 == CKM_AES_KEY_WRAP {
+        match aes_key_wrap(&key, &private_key) {
+            Ok(wrapped) => wrapped,
+            Err(err) => return err.into_ck_rv(),
+        }
+    } else if mechanism == CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP {
+        encrypt(&key, private_key).into_combined()
+    } else {
+        return CKR_MECHANISM_INVALID as CK_RV;
+    };
 
-    let encryption_output = encrypt(key, private_key);
-    let ciphertext_with_iv = encryption_output.into_combined();
     unsafe {
-        *pulWrappedKeyLen = ciphertext_with_iv.len() as CK_ULONG;
+        *pulWrappedKeyLen = wrapped.len() as CK_ULONG;
     }
 
     if pWrappedKey.is_null() {
@@ -149,15 +171,11 @@ pub(crate) fn C_WrapKey(
     }
 
     unsafe {
-        ptr::copy(
-            ciphertext_with_iv.as_ptr(),
-            pWrappedKey,
-            ciphertext_with_iv.len(),
-        );
+        ptr::copy(wrapped.as_ptr(), pWrappedKey, wrapped.len());
     }
 
-    // TODO: either buffer ciphertext length or only precompute it if pWrappedKey is null
-    // now encryption is done twice
+    // TODO: either buffer the wrapped key length or only precompute it if pWrappedKey is null
+    // now wrapping is done twice
     CKR_OK as CK_RV
 }
 
@@ -184,31 +202,60 @@ pub(crate) fn C_UnwrapKey(
     ulAttributeCount: CK_ULONG,
     phKey: CK_OBJECT_HANDLE_PTR,
 ) -> CK_RV {
-    if pWrappedKey.is_null() {
+    if pMechanism.is_null() || pWrappedKey.is_null() {
         return CKR_ARGUMENTS_BAD as CK_RV;
     }
+    let mechanism = unsafe { *pMechanism }.mechanism;
 
-    let state_accessor = StateAccessor::new();
-    let unwrapping_key = match state_accessor.get_object(&hSession, &hUnwrappingKey) {
+    let manager_proxy = ManagerProxy::new();
+    let unwrapping_key = match manager_proxy.get_object(&hSession, &hUnwrappingKey) {
         Ok(val) => val,
         Err(err) => return err.into_ck_rv(),
     };
 
     let key = unwrapping_key.get_value().unwrap();
-    let encryption_output =
-        unsafe { destructure_iv_ciphertext(pWrappedKey, ulWrappedKeyLen as usize) };
+    let wrapped = unsafe { Vec::from_pointer(pWrappedKey, ulWrappedKeyLen as usize) };
 
-    let plaintext = decrypt(&key, encryption_output.ciphertext, encryption_output.iv);
+    let plaintext = if mechanism as u32 == CKM_AES_KEY_WRAP {
+        match aes_key_unwrap(&key, &wrapped) {
+            Ok(plaintext) => plaintext,
+            Err(err) => return err.into_ck_rv(),
+        }
+    } else if mechanism == CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP {
+        let encryption_output =
+            unsafe { destructure_iv_ciphertext(pWrappedKey, ulWrappedKeyLen as usize) };
+        decrypt(&key, encryption_output.ciphertext, encryption_output.iv)
+    } else {
+        return CKR_MECHANISM_INVALID as CK_RV;
+    };
 
-    // TODO: create from template
-    let mut private_key_object = PrivateKeyObject::new();
-    private_key_object.store_value(plaintext);
+    let attributes = unsafe { Vec::from_pointer(pTemplate, ulAttributeCount as usize) };
+    let template = Template::from(attributes);
 
-    let handle =
-        match state_accessor.create_ephemeral_object(&hSession, Arc::new(private_key_object)) {
-            Ok(val) => val,
-            Err(err) => return err.into_ck_rv(),
-        };
+    let class = template.class();
+    let key_type = template.key_type();
+    if key_type == Some(CKK_AES) && plaintext.len() != AES_BLOCK_SIZE {
+        return CKR_TEMPLATE_INCONSISTENT as CK_RV;
+    }
+
+    let object: Arc<dyn CryptokiObject> = match class {
+        Some(CKO_SECRET_KEY) => {
+            let mut object = SecretKeyObject::from_template(template);
+            object.store_value(plaintext);
+            Arc::new(object)
+        }
+        Some(CKO_PRIVATE_KEY) | None => {
+            let mut object = PrivateKeyObject::from_template(template);
+            object.store_value(plaintext);
+            Arc::new(object)
+        }
+        Some(_) => return CKR_TEMPLATE_INCONSISTENT as CK_RV,
+    };
+
+    let handle = match manager_proxy.create_ephemeral_object(&hSession, object) {
+        Ok(val) => val,
+        Err(err) => return err.into_ck_rv(),
+    };
     unsafe {
         *phKey = handle;
     }
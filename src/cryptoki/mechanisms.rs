@@ -0,0 +1,181 @@
+use super::bindings::{
+    CKF_DECRYPT, CKF_ENCRYPT, CKF_GENERATE, CKF_SIGN, CKF_UNWRAP, CKF_WRAP, CKM_AES_CBC,
+    CKM_AES_CBC_PAD, CKM_AES_KEY_GEN, CKM_AES_KEY_WRAP, CKM_RSA_PKCS, CKR_ARGUMENTS_BAD,
+    CKR_BUFFER_TOO_SMALL, CKR_MECHANISM_INVALID, CKR_OK, CK_MECHANISM_INFO, CK_MECHANISM_INFO_PTR,
+    CK_MECHANISM_TYPE, CK_MECHANISM_TYPE_PTR, CK_RV, CK_SLOT_ID, CK_ULONG, CK_ULONG_PTR,
+};
+use super::key_management::CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP;
+
+/// Mechanisms reported through `C_GetMechanismList`/`C_GetMechanismInfo`.
+///
+/// Every slot supports the same set of mechanisms, so this table is not keyed by `CK_SLOT_ID`;
+/// `C_GetMechanismList` and `C_GetMechanismInfo` accept a `slotID` but do not look at it.
+const MECHANISMS: &[(CK_MECHANISM_TYPE, CK_MECHANISM_INFO)] = &[
+    (
+        CKM_AES_KEY_GEN as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            // AES key sizes are reported in bytes, not bits (unlike RSA below).
+            ulMinKeySize: 16,
+            ulMaxKeySize: 16,
+            flags: CKF_GENERATE,
+        },
+    ),
+    (
+        CKM_AES_CBC as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            ulMinKeySize: 16,
+            ulMaxKeySize: 16,
+            flags: CKF_ENCRYPT | CKF_DECRYPT,
+        },
+    ),
+    (
+        CKM_AES_CBC_PAD as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            ulMinKeySize: 16,
+            ulMaxKeySize: 16,
+            flags: CKF_ENCRYPT | CKF_DECRYPT,
+        },
+    ),
+    (
+        CKM_RSA_PKCS as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            ulMinKeySize: 2048,
+            ulMaxKeySize: 4096,
+            flags: CKF_SIGN,
+        },
+    ),
+    (
+        CKM_AES_KEY_WRAP as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            ulMinKeySize: 16,
+            ulMaxKeySize: 16,
+            flags: CKF_WRAP | CKF_UNWRAP,
+        },
+    ),
+    (
+        CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP as CK_MECHANISM_TYPE,
+        CK_MECHANISM_INFO {
+            ulMinKeySize: 16,
+            ulMaxKeySize: 16,
+            flags: CKF_WRAP | CKF_UNWRAP,
+        },
+    ),
+];
+
+fn mechanism_info(mechanism_type: CK_MECHANISM_TYPE) -> Option<CK_MECHANISM_INFO> {
+    MECHANISMS
+        .iter()
+        .find(|(mechanism, _)| *mechanism == mechanism_type)
+        .map(|(_, info)| *info)
+}
+
+/// Obtains a list of mechanism types supported by a token
+///
+/// # Arguments
+///
+/// * `slotID` - the ID of the token’s slot
+/// * `pMechanismList` - points to the location that receives the mechanism type array
+/// * `pulCount` - points to the location that receives the number of mechanisms
+#[allow(non_snake_case)]
+pub(crate) fn C_GetMechanismList(
+    _slotID: CK_SLOT_ID,
+    pMechanismList: CK_MECHANISM_TYPE_PTR,
+    pulCount: CK_ULONG_PTR,
+) -> CK_RV {
+    if pulCount.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+
+    if pMechanismList.is_null() {
+        unsafe {
+            *pulCount = MECHANISMS.len() as CK_ULONG;
+        }
+        return CKR_OK as CK_RV;
+    }
+
+    if (unsafe { *pulCount } as usize) < MECHANISMS.len() {
+        unsafe {
+            *pulCount = MECHANISMS.len() as CK_ULONG;
+        }
+        return CKR_BUFFER_TOO_SMALL as CK_RV;
+    }
+
+    unsafe {
+        for (i, (mechanism, _)) in MECHANISMS.iter().enumerate() {
+            *pMechanismList.add(i) = *mechanism;
+        }
+        *pulCount = MECHANISMS.len() as CK_ULONG;
+    }
+
+    CKR_OK as CK_RV
+}
+
+/// Obtains information about a particular mechanism
+///
+/// # Arguments
+///
+/// * `slotID` - the ID of the token’s slot
+/// * `type` - the type of mechanism
+/// * `pInfo` - points to the location that receives the mechanism information
+#[allow(non_snake_case)]
+pub(crate) fn C_GetMechanismInfo(
+    _slotID: CK_SLOT_ID,
+    type_: CK_MECHANISM_TYPE,
+    pInfo: CK_MECHANISM_INFO_PTR,
+) -> CK_RV {
+    if pInfo.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+    let info = match mechanism_info(type_) {
+        Some(info) => info,
+        None => return CKR_MECHANISM_INVALID as CK_RV,
+    };
+
+    unsafe {
+        *pInfo = info;
+    }
+
+    CKR_OK as CK_RV
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aes_mechanisms_report_key_size_in_bytes() {
+        for mechanism in [CKM_AES_KEY_GEN, CKM_AES_CBC, CKM_AES_CBC_PAD, CKM_AES_KEY_WRAP] {
+            let info = mechanism_info(mechanism as CK_MECHANISM_TYPE).unwrap();
+            assert_eq!(info.ulMinKeySize, 16, "mechanism {mechanism}");
+            assert_eq!(info.ulMaxKeySize, 16, "mechanism {mechanism}");
+        }
+    }
+
+    #[test]
+    fn aes_cbc_mechanisms_support_both_encrypt_and_decrypt() {
+        for mechanism in [CKM_AES_CBC, CKM_AES_CBC_PAD] {
+            let info = mechanism_info(mechanism as CK_MECHANISM_TYPE).unwrap();
+            assert_eq!(
+                info.flags,
+                CKF_ENCRYPT | CKF_DECRYPT,
+                "mechanism {mechanism}"
+            );
+        }
+    }
+
+    #[test]
+    fn vendor_wrap_mechanism_is_reported() {
+        let info =
+            mechanism_info(CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP as CK_MECHANISM_TYPE).unwrap();
+        assert_eq!(info.flags, CKF_WRAP | CKF_UNWRAP);
+        let vendor_mechanism = CKM_CRYPTOKI_BRIDGE_AES_CBC_WRAP as CK_MECHANISM_TYPE;
+        assert!(MECHANISMS
+            .iter()
+            .any(|(mechanism, _)| *mechanism == vendor_mechanism));
+    }
+
+    #[test]
+    fn unknown_mechanism_has_no_info() {
+        assert!(mechanism_info(CK_MECHANISM_TYPE::MAX).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use super::bindings::{
+    CKF_TOKEN_PRESENT, CKR_ARGUMENTS_BAD, CKR_BUFFER_TOO_SMALL, CKR_OK, CKR_SLOT_ID_INVALID,
+    CK_BBOOL, CK_FLAGS, CK_RV, CK_SLOT_ID, CK_SLOT_ID_PTR, CK_SLOT_INFO, CK_SLOT_INFO_PTR,
+    CK_TOKEN_INFO, CK_TOKEN_INFO_PTR, CK_ULONG, CK_ULONG_PTR, CK_VERSION,
+};
+
+/// A single configured threshold-signing group, exposed to the host as its own slot/token.
+///
+/// Every session is opened against exactly one slot, so `C_GenerateKeyPair`, `C_Sign`, and object
+/// searches made through that session only ever see the matching group's keypair.
+struct SlotDescription {
+    slot_id: CK_SLOT_ID,
+    slot_description: &'static str,
+    token_label: &'static str,
+}
+
+// TODO: load the configured groups instead of hard-coding two placeholder slots
+const SLOTS: &[SlotDescription] = &[
+    SlotDescription {
+        slot_id: 0,
+        slot_description: "threshold signing group 0",
+        token_label: "group-0",
+    },
+    SlotDescription {
+        slot_id: 1,
+        slot_description: "threshold signing group 1",
+        token_label: "group-1",
+    },
+];
+
+fn slot_description(slot_id: CK_SLOT_ID) -> Option<&'static SlotDescription> {
+    SLOTS.iter().find(|slot| slot.slot_id == slot_id)
+}
+
+fn pad_into(bytes: &[u8], buffer: &mut [u8]) {
+    let len = bytes.len().min(buffer.len());
+    buffer[..len].copy_from_slice(&bytes[..len]);
+}
+
+/// Obtains a list of slots in the system
+///
+/// # Arguments
+///
+/// * `tokenPresent` - only slots with a token present are returned when this is true
+/// * `pSlotList` - points to the location that receives the slot list
+/// * `pulCount` - points to the location that receives the number of slots
+#[allow(non_snake_case)]
+pub(crate) fn C_GetSlotList(
+    _tokenPresent: CK_BBOOL,
+    pSlotList: CK_SLOT_ID_PTR,
+    pulCount: CK_ULONG_PTR,
+) -> CK_RV {
+    if pulCount.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+
+    // every configured slot always has its token present, so tokenPresent does not filter here
+    if pSlotList.is_null() {
+        unsafe {
+            *pulCount = SLOTS.len() as CK_ULONG;
+        }
+        return CKR_OK as CK_RV;
+    }
+
+    if (unsafe { *pulCount } as usize) < SLOTS.len() {
+        unsafe {
+            *pulCount = SLOTS.len() as CK_ULONG;
+        }
+        return CKR_BUFFER_TOO_SMALL as CK_RV;
+    }
+
+    unsafe {
+        for (i, slot) in SLOTS.iter().enumerate() {
+            *pSlotList.add(i) = slot.slot_id;
+        }
+        *pulCount = SLOTS.len() as CK_ULONG;
+    }
+
+    CKR_OK as CK_RV
+}
+
+/// Obtains information about a particular slot
+///
+/// # Arguments
+///
+/// * `slotID` - the ID of the slot
+/// * `pInfo` - points to the location that receives the slot information
+#[allow(non_snake_case)]
+pub(crate) fn C_GetSlotInfo(slotID: CK_SLOT_ID, pInfo: CK_SLOT_INFO_PTR) -> CK_RV {
+    if pInfo.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+    let slot = match slot_description(slotID) {
+        Some(slot) => slot,
+        None => return CKR_SLOT_ID_INVALID as CK_RV,
+    };
+
+    let mut info = CK_SLOT_INFO {
+        slotDescription: [0x20; 64],
+        manufacturerID: [0x20; 32],
+        flags: CKF_TOKEN_PRESENT as CK_FLAGS,
+        hardwareVersion: CK_VERSION { major: 0, minor: 1 },
+        firmwareVersion: CK_VERSION { major: 0, minor: 1 },
+    };
+    pad_into(slot.slot_description.as_bytes(), &mut info.slotDescription);
+
+    unsafe {
+        *pInfo = info;
+    }
+    CKR_OK as CK_RV
+}
+
+/// Obtains information about a particular token
+///
+/// # Arguments
+///
+/// * `slotID` - the ID of the slot whose token is to be queried
+/// * `pInfo` - points to the location that receives the token information
+#[allow(non_snake_case)]
+pub(crate) fn C_GetTokenInfo(slotID: CK_SLOT_ID, pInfo: CK_TOKEN_INFO_PTR) -> CK_RV {
+    if pInfo.is_null() {
+        return CKR_ARGUMENTS_BAD as CK_RV;
+    }
+    let slot = match slot_description(slotID) {
+        Some(slot) => slot,
+        None => return CKR_SLOT_ID_INVALID as CK_RV,
+    };
+
+    let mut info = CK_TOKEN_INFO {
+        label: [0x20; 32],
+        manufacturerID: [0x20; 32],
+        model: [0x20; 16],
+        serialNumber: [0x20; 16],
+        flags: 0,
+        ulMaxSessionCount: !0,
+        ulSessionCount: !0,
+        ulMaxRwSessionCount: !0,
+        ulRwSessionCount: !0,
+        ulMaxPinLen: 0,
+        ulMinPinLen: 0,
+        ulTotalPublicMemory: !0,
+        ulFreePublicMemory: !0,
+        ulTotalPrivateMemory: !0,
+        ulFreePrivateMemory: !0,
+        hardwareVersion: CK_VERSION { major: 0, minor: 1 },
+        firmwareVersion: CK_VERSION { major: 0, minor: 1 },
+        utcTime: [0x20; 16],
+    };
+    pad_into(slot.token_label.as_bytes(), &mut info.label);
+
+    unsafe {
+        *pInfo = info;
+    }
+    CKR_OK as CK_RV
+}
+
+/// Returns whether `slot_id` names one of the configured slots, i.e. whether a session may be
+/// opened against it.
+pub(crate) fn is_valid_slot(slot_id: CK_SLOT_ID) -> bool {
+    slot_description(slot_id).is_some()
+}
+
+/// The slot a session is scoped to before it has been explicitly bound to one via `C_OpenSession`.
+pub(crate) fn default_slot() -> CK_SLOT_ID {
+    SLOTS[0].slot_id
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_configured_slot_is_valid() {
+        for slot in SLOTS {
+            assert!(is_valid_slot(slot.slot_id));
+        }
+    }
+
+    #[test]
+    fn an_unconfigured_slot_is_not_valid() {
+        assert!(!is_valid_slot(CK_SLOT_ID::MAX));
+    }
+
+    #[test]
+    fn default_slot_is_valid() {
+        assert!(is_valid_slot(default_slot()));
+    }
+
+    #[test]
+    fn c_get_slot_list_reports_every_configured_slot() {
+        let mut slots = vec![0 as CK_SLOT_ID; SLOTS.len()];
+        let mut count = slots.len() as CK_ULONG;
+        let rv = C_GetSlotList(0, slots.as_mut_ptr(), &mut count);
+        assert_eq!(rv, CKR_OK as CK_RV);
+        assert_eq!(count as usize, SLOTS.len());
+        assert_eq!(slots, SLOTS.iter().map(|s| s.slot_id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn c_get_slot_info_rejects_an_unconfigured_slot() {
+        let mut info = CK_SLOT_INFO {
+            slotDescription: [0; 64],
+            manufacturerID: [0; 32],
+            flags: 0,
+            hardwareVersion: CK_VERSION { major: 0, minor: 0 },
+            firmwareVersion: CK_VERSION { major: 0, minor: 0 },
+        };
+        assert_eq!(
+            C_GetSlotInfo(CK_SLOT_ID::MAX, &mut info),
+            CKR_SLOT_ID_INVALID as CK_RV
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use rand::{rngs::OsRng, Rng};
+
+use super::{
+    object::{
+        cryptoki_object::CryptokiObject, private_key_object::PrivateKeyObject,
+        public_key_object::PublicKeyObject,
+    },
+    object::template::Template,
+    Error,
+};
+use crate::cryptoki::bindings::CK_SLOT_ID;
+
+/// Source of key material and signing capability for the module.
+///
+/// `StateAccessor` holds a `Box<dyn Backend>` rather than talking to the remote threshold-signing
+/// communicator directly, so a local/in-memory backend can stand in during tests and so other key
+/// sources can be plugged in without touching the Cryptoki-facing code. Every method takes the
+/// `CK_SLOT_ID` the calling session was opened against, so a single backend instance can serve all
+/// of the module's configured threshold-signing groups without mixing up their keypairs.
+pub(crate) trait Backend: Send {
+    /// Finds the objects matching `query` within `slot`, analogous to what `C_FindObjectsInit`
+    /// searches over.
+    fn find_objects(
+        &self,
+        slot: CK_SLOT_ID,
+        query: &Template,
+    ) -> Result<Vec<Arc<dyn CryptokiObject>>, Error>;
+
+    /// Returns `slot`'s configured keypair as `(private, public)`.
+    fn get_keypair(
+        &self,
+        slot: CK_SLOT_ID,
+    ) -> Result<(Arc<dyn CryptokiObject>, Arc<dyn CryptokiObject>), Error>;
+
+    /// Signs `data` with `key`, which must be one of the objects `slot` returned.
+    fn sign(
+        &self,
+        slot: CK_SLOT_ID,
+        key: &Arc<dyn CryptokiObject>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// Backend that forwards key operations to the external threshold-signing communicator.
+///
+/// This is the module's default backend (see [`default_backend`]); it is kept as its own type so the
+/// communicator client can be swapped out or mocked without touching `StateAccessor`.
+pub(crate) struct RemoteBackend {
+    // TODO: hold the communicator client handle once it is threaded through here
+}
+
+impl RemoteBackend {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Backend for RemoteBackend {
+    fn find_objects(
+        &self,
+        _slot: CK_SLOT_ID,
+        _query: &Template,
+    ) -> Result<Vec<Arc<dyn CryptokiObject>>, Error> {
+        todo!("forward to the threshold-signing communicator")
+    }
+
+    fn get_keypair(
+        &self,
+        _slot: CK_SLOT_ID,
+    ) -> Result<(Arc<dyn CryptokiObject>, Arc<dyn CryptokiObject>), Error> {
+        todo!("forward to the threshold-signing communicator")
+    }
+
+    fn sign(
+        &self,
+        _slot: CK_SLOT_ID,
+        _key: &Arc<dyn CryptokiObject>,
+        _data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        todo!("forward to the threshold-signing communicator")
+    }
+}
+
+/// In-memory backend with no external dependencies, used by tests and by anyone who wants to run
+/// the module without a live communicator. Generates one keypair per configured slot, lazily, the
+/// first time that slot is used.
+pub(crate) struct LocalBackend {
+    keypairs: Mutex<HashMap<CK_SLOT_ID, (Arc<dyn CryptokiObject>, Arc<dyn CryptokiObject>)>>,
+}
+
+impl LocalBackend {
+    pub(crate) fn new() -> Self {
+        Self {
+            keypairs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Backend for LocalBackend {
+    fn find_objects(
+        &self,
+        _slot: CK_SLOT_ID,
+        _query: &Template,
+    ) -> Result<Vec<Arc<dyn CryptokiObject>>, Error> {
+        // This backend doesn't keep a store of created objects to search over yet; only the
+        // per-slot keypair from `get_keypair` is available.
+        Ok(Vec::new())
+    }
+
+    fn get_keypair(
+        &self,
+        slot: CK_SLOT_ID,
+    ) -> Result<(Arc<dyn CryptokiObject>, Arc<dyn CryptokiObject>), Error> {
+        let mut keypairs = self.keypairs.lock().unwrap();
+        let keypair = keypairs.entry(slot).or_insert_with(|| {
+            let mut private_key_object = PrivateKeyObject::new();
+            let key: [u8; 32] = OsRng.gen();
+            private_key_object.store_value(key.into());
+
+            // No real asymmetric keypair is generated locally; the public half is a distinct
+            // placeholder object rather than the private scalar under another handle.
+            let mut public_key_object = PublicKeyObject::new();
+            let public_placeholder: [u8; 32] = OsRng.gen();
+            public_key_object.store_value(public_placeholder.into());
+
+            (
+                Arc::new(private_key_object) as Arc<dyn CryptokiObject>,
+                Arc::new(public_key_object) as Arc<dyn CryptokiObject>,
+            )
+        });
+        Ok(keypair.clone())
+    }
+
+    fn sign(
+        &self,
+        _slot: CK_SLOT_ID,
+        _key: &Arc<dyn CryptokiObject>,
+        data: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        // No real signature scheme is available locally; echoing the digest back is enough for
+        // exercising the Cryptoki call sequence without a live communicator.
+        Ok(data.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn get_keypair_does_not_reuse_the_private_key_as_the_public_key() {
+        let backend = LocalBackend::new();
+        let (private_key, public_key) = backend.get_keypair(0).unwrap();
+        assert_ne!(
+            private_key.get_value().unwrap(),
+            public_key.get_value().unwrap()
+        );
+    }
+
+    #[test]
+    fn get_keypair_is_stable_for_the_same_slot() {
+        let backend = LocalBackend::new();
+        let first = backend.get_keypair(0).unwrap();
+        let second = backend.get_keypair(0).unwrap();
+        assert_eq!(first.0.get_value().unwrap(), second.0.get_value().unwrap());
+        assert_eq!(first.1.get_value().unwrap(), second.1.get_value().unwrap());
+    }
+
+    #[test]
+    fn get_keypair_differs_across_slots() {
+        let backend = LocalBackend::new();
+        let slot_0 = backend.get_keypair(0).unwrap();
+        let slot_1 = backend.get_keypair(1).unwrap();
+        assert_ne!(slot_0.0.get_value().unwrap(), slot_1.0.get_value().unwrap());
+    }
+
+    #[test]
+    fn find_objects_does_not_panic_with_no_store_to_search() {
+        let backend = LocalBackend::new();
+        let empty_query = Template::from(Vec::new());
+        assert_eq!(backend.find_objects(0, &empty_query).unwrap().len(), 0);
+    }
+}
+
+/// Chooses a backend for `C_Initialize` to hand to `StateAccessor`.
+///
+/// The remote threshold-signing communicator is the default; setting
+/// `CRYPTOKI_BRIDGE_BACKEND=local` switches to [`LocalBackend`] for local development and tests.
+pub(crate) fn default_backend() -> Box<dyn Backend> {
+    match std::env::var("CRYPTOKI_BRIDGE_BACKEND").as_deref() {
+        Ok("local") => Box::new(LocalBackend::new()),
+        _ => Box::new(RemoteBackend::new()),
+    }
+}
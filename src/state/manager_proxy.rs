@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use std::sync::Arc;
+
+use super::{backend::Backend, object::cryptoki_object::CryptokiObject, Error, StateAccessor};
+use crate::cryptoki::{
+    bindings::{
+        CKR_OK, CKR_SLOT_ID_INVALID, CK_OBJECT_HANDLE, CK_RV, CK_SESSION_HANDLE, CK_SLOT_ID,
+    },
+    encryption::{DecryptionContext, EncryptionContext},
+    slots::{default_slot, is_valid_slot},
+};
+
+/// A single request the worker thread knows how to serve, together with the channel its result
+/// should be sent back on. Keeping this as one enum (rather than a closure per call) is what lets
+/// the worker own `StateAccessor` without it ever crossing a thread boundary.
+enum Request {
+    InitializeState {
+        backend: Box<dyn Backend>,
+        reply: mpsc::Sender<Result<(), Error>>,
+    },
+    Finalize {
+        reply: mpsc::Sender<Result<(), Error>>,
+    },
+    CreateObject {
+        session: CK_SESSION_HANDLE,
+        object: Arc<dyn CryptokiObject>,
+        reply: mpsc::Sender<Result<CK_OBJECT_HANDLE, Error>>,
+    },
+    CreateEphemeralObject {
+        session: CK_SESSION_HANDLE,
+        object: Arc<dyn CryptokiObject>,
+        reply: mpsc::Sender<Result<CK_OBJECT_HANDLE, Error>>,
+    },
+    GetObject {
+        session: CK_SESSION_HANDLE,
+        handle: CK_OBJECT_HANDLE,
+        reply: mpsc::Sender<Result<Arc<dyn CryptokiObject>, Error>>,
+    },
+    GetKeypair {
+        session: CK_SESSION_HANDLE,
+        slot: CK_SLOT_ID,
+        reply: mpsc::Sender<Result<(CK_OBJECT_HANDLE, CK_OBJECT_HANDLE), Error>>,
+    },
+    SetEncryptionContext {
+        session: CK_SESSION_HANDLE,
+        context: EncryptionContext,
+        reply: mpsc::Sender<Result<(), Error>>,
+    },
+    TakeEncryptionContext {
+        session: CK_SESSION_HANDLE,
+        reply: mpsc::Sender<Result<EncryptionContext, Error>>,
+    },
+    SetDecryptionContext {
+        session: CK_SESSION_HANDLE,
+        context: DecryptionContext,
+        reply: mpsc::Sender<Result<(), Error>>,
+    },
+    TakeDecryptionContext {
+        session: CK_SESSION_HANDLE,
+        reply: mpsc::Sender<Result<DecryptionContext, Error>>,
+    },
+}
+
+/// Slot each open session is currently bound to, keyed by session handle.
+///
+/// This is kept separate from the `StateAccessor` worker thread: binding a session to a slot is
+/// plain shared state that doesn't need to be serialized against backend calls, so a session's
+/// binding can be looked up without a channel round-trip.
+fn session_slots() -> &'static Mutex<HashMap<CK_SESSION_HANDLE, CK_SLOT_ID>> {
+    static SESSION_SLOTS: OnceLock<Mutex<HashMap<CK_SESSION_HANDLE, CK_SLOT_ID>>> =
+        OnceLock::new();
+    SESSION_SLOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Hands out a fresh, never-reused session handle for `C_OpenSession`.
+fn next_session_handle() -> CK_SESSION_HANDLE {
+    static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+    NEXT_HANDLE.fetch_add(1, Ordering::Relaxed) as CK_SESSION_HANDLE
+}
+
+fn worker_sender() -> &'static mpsc::Sender<Request> {
+    static SENDER: OnceLock<mpsc::Sender<Request>> = OnceLock::new();
+    SENDER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Request>();
+        std::thread::spawn(move || {
+            // `StateAccessor` and everything it touches now lives exclusively on this thread,
+            // so every Cryptoki call is implicitly serialized against it.
+            let mut state_accessor = StateAccessor::new();
+            for request in receiver {
+                match request {
+                    Request::InitializeState { backend, reply } => {
+                        let _ = reply.send(state_accessor.initialize_state(backend));
+                    }
+                    Request::Finalize { reply } => {
+                        let _ = reply.send(state_accessor.finalize());
+                    }
+                    Request::CreateObject {
+                        session,
+                        object,
+                        reply,
+                    } => {
+                        let _ = reply.send(state_accessor.create_object(&session, object));
+                    }
+                    Request::CreateEphemeralObject {
+                        session,
+                        object,
+                        reply,
+                    } => {
+                        let _ =
+                            reply.send(state_accessor.create_ephemeral_object(&session, object));
+                    }
+                    Request::GetObject {
+                        session,
+                        handle,
+                        reply,
+                    } => {
+                        let _ = reply.send(state_accessor.get_object(&session, &handle));
+                    }
+                    Request::GetKeypair {
+                        session,
+                        slot,
+                        reply,
+                    } => {
+                        let _ = reply.send(state_accessor.get_keypair(&session, slot));
+                    }
+                    Request::SetEncryptionContext {
+                        session,
+                        context,
+                        reply,
+                    } => {
+                        let _ = reply
+                            .send(state_accessor.set_encryption_context(&session, context));
+                    }
+                    Request::TakeEncryptionContext { session, reply } => {
+                        let _ = reply.send(state_accessor.take_encryption_context(&session));
+                    }
+                    Request::SetDecryptionContext {
+                        session,
+                        context,
+                        reply,
+                    } => {
+                        let _ = reply
+                            .send(state_accessor.set_decryption_context(&session, context));
+                    }
+                    Request::TakeDecryptionContext { session, reply } => {
+                        let _ = reply.send(state_accessor.take_decryption_context(&session));
+                    }
+                }
+            }
+        });
+        sender
+    })
+}
+
+/// Thin, cloneable handle to the state-owning worker thread.
+///
+/// `ManagerProxy` has the same method surface as `StateAccessor` did, so it is a drop-in
+/// replacement at every call site: constructing one is just cloning an `mpsc::Sender`, and every
+/// method packages its arguments into a [`Request`], sends it to the worker, and blocks on a
+/// oneshot reply channel for the result. This keeps re-entrant or concurrent Cryptoki calls from
+/// racing on the shared session/object state, at the cost of one channel round-trip per call.
+#[derive(Clone)]
+pub(crate) struct ManagerProxy {
+    sender: mpsc::Sender<Request>,
+}
+
+impl ManagerProxy {
+    pub(crate) fn new() -> Self {
+        Self {
+            sender: worker_sender().clone(),
+        }
+    }
+
+    fn call<T>(
+        &self,
+        build_request: impl FnOnce(mpsc::Sender<Result<T, Error>>) -> Request,
+    ) -> Result<T, Error> {
+        let (reply, receiver) = mpsc::channel();
+        self.sender
+            .send(build_request(reply))
+            .expect("state worker thread terminated");
+        receiver.recv().expect("state worker thread terminated")
+    }
+
+    pub(crate) fn initialize_state(&self, backend: Box<dyn Backend>) -> Result<(), Error> {
+        self.call(|reply| Request::InitializeState { backend, reply })
+    }
+
+    pub(crate) fn finalize(&self) -> Result<(), Error> {
+        self.call(|reply| Request::Finalize { reply })
+    }
+
+    pub(crate) fn create_object(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        object: Arc<dyn CryptokiObject>,
+    ) -> Result<CK_OBJECT_HANDLE, Error> {
+        let session = *session;
+        self.call(|reply| Request::CreateObject {
+            session,
+            object,
+            reply,
+        })
+    }
+
+    pub(crate) fn create_ephemeral_object(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        object: Arc<dyn CryptokiObject>,
+    ) -> Result<CK_OBJECT_HANDLE, Error> {
+        let session = *session;
+        self.call(|reply| Request::CreateEphemeralObject {
+            session,
+            object,
+            reply,
+        })
+    }
+
+    pub(crate) fn get_object(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        handle: &CK_OBJECT_HANDLE,
+    ) -> Result<Arc<dyn CryptokiObject>, Error> {
+        let session = *session;
+        let handle = *handle;
+        self.call(|reply| Request::GetObject {
+            session,
+            handle,
+            reply,
+        })
+    }
+
+    pub(crate) fn get_keypair(
+        &self,
+        session: &CK_SESSION_HANDLE,
+    ) -> Result<(CK_OBJECT_HANDLE, CK_OBJECT_HANDLE), Error> {
+        let slot = self.session_slot(session);
+        let session = *session;
+        self.call(|reply| Request::GetKeypair { session, slot, reply })
+    }
+
+    /// Binds `session` to `slot`, so later calls scoped to `session` (key generation, signing,
+    /// object searches) resolve to that slot's threshold-signing group instead of the default one.
+    ///
+    /// Rebinds an existing handle; `C_OpenSession` binds new handles through [`Self::open_session`].
+    pub(crate) fn bind_session_to_slot(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        slot: CK_SLOT_ID,
+    ) -> CK_RV {
+        if !is_valid_slot(slot) {
+            return CKR_SLOT_ID_INVALID as CK_RV;
+        }
+        session_slots().lock().unwrap().insert(*session, slot);
+        CKR_OK as CK_RV
+    }
+
+    /// Forgets `session`'s slot binding. Called by `C_CloseSession`.
+    pub(crate) fn unbind_session(&self, session: &CK_SESSION_HANDLE) {
+        session_slots().lock().unwrap().remove(session);
+    }
+
+    /// Allocates a new session handle bound to `slot`, for `C_OpenSession` to hand back to the
+    /// caller.
+    pub(crate) fn open_session(&self, slot: CK_SLOT_ID) -> Result<CK_SESSION_HANDLE, CK_RV> {
+        if !is_valid_slot(slot) {
+            return Err(CKR_SLOT_ID_INVALID as CK_RV);
+        }
+        let session = next_session_handle();
+        session_slots().lock().unwrap().insert(session, slot);
+        Ok(session)
+    }
+
+    /// The slot `session` is bound to, or the default slot if it is not bound (e.g. because it
+    /// did not come from [`Self::open_session`], or was unbound by `C_CloseSession`).
+    fn session_slot(&self, session: &CK_SESSION_HANDLE) -> CK_SLOT_ID {
+        session_slots()
+            .lock()
+            .unwrap()
+            .get(session)
+            .copied()
+            .unwrap_or_else(default_slot)
+    }
+
+    pub(crate) fn set_encryption_context(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        context: EncryptionContext,
+    ) -> Result<(), Error> {
+        let session = *session;
+        self.call(|reply| Request::SetEncryptionContext {
+            session,
+            context,
+            reply,
+        })
+    }
+
+    pub(crate) fn take_encryption_context(
+        &self,
+        session: &CK_SESSION_HANDLE,
+    ) -> Result<EncryptionContext, Error> {
+        let session = *session;
+        self.call(|reply| Request::TakeEncryptionContext { session, reply })
+    }
+
+    pub(crate) fn set_decryption_context(
+        &self,
+        session: &CK_SESSION_HANDLE,
+        context: DecryptionContext,
+    ) -> Result<(), Error> {
+        let session = *session;
+        self.call(|reply| Request::SetDecryptionContext {
+            session,
+            context,
+            reply,
+        })
+    }
+
+    pub(crate) fn take_decryption_context(
+        &self,
+        session: &CK_SESSION_HANDLE,
+    ) -> Result<DecryptionContext, Error> {
+        let session = *session;
+        self.call(|reply| Request::TakeDecryptionContext { session, reply })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // `session_slots()` is a single process-wide map, so give each test its own session handle to
+    // avoid interfering with the others. The fixed handles count down from the top of the range so
+    // they never collide with the ones `open_session` hands out counting up from 1.
+    #[test]
+    fn unbound_session_resolves_to_the_default_slot() {
+        let manager_proxy = ManagerProxy::new();
+        let session: CK_SESSION_HANDLE = CK_SESSION_HANDLE::MAX;
+        assert_eq!(manager_proxy.session_slot(&session), default_slot());
+    }
+
+    #[test]
+    fn bind_session_to_slot_is_reflected_by_session_slot() {
+        let manager_proxy = ManagerProxy::new();
+        let session: CK_SESSION_HANDLE = CK_SESSION_HANDLE::MAX - 1;
+        assert_eq!(
+            manager_proxy.bind_session_to_slot(&session, 1),
+            CKR_OK as CK_RV
+        );
+        assert_eq!(manager_proxy.session_slot(&session), 1);
+    }
+
+    #[test]
+    fn bind_session_to_slot_rejects_an_unknown_slot() {
+        let manager_proxy = ManagerProxy::new();
+        let session: CK_SESSION_HANDLE = CK_SESSION_HANDLE::MAX - 2;
+        assert_eq!(
+            manager_proxy.bind_session_to_slot(&session, CK_SLOT_ID::MAX),
+            CKR_SLOT_ID_INVALID as CK_RV
+        );
+        assert_eq!(manager_proxy.session_slot(&session), default_slot());
+    }
+
+    #[test]
+    fn unbind_session_reverts_to_the_default_slot() {
+        let manager_proxy = ManagerProxy::new();
+        let session: CK_SESSION_HANDLE = CK_SESSION_HANDLE::MAX - 3;
+        manager_proxy.bind_session_to_slot(&session, 1);
+        manager_proxy.unbind_session(&session);
+        assert_eq!(manager_proxy.session_slot(&session), default_slot());
+    }
+
+    #[test]
+    fn open_session_binds_the_returned_handle_to_the_requested_slot() {
+        let manager_proxy = ManagerProxy::new();
+        let session = manager_proxy.open_session(1).unwrap();
+        assert_eq!(manager_proxy.session_slot(&session), 1);
+    }
+
+    #[test]
+    fn open_session_rejects_an_unknown_slot() {
+        let manager_proxy = ManagerProxy::new();
+        assert_eq!(
+            manager_proxy.open_session(CK_SLOT_ID::MAX).unwrap_err(),
+            CKR_SLOT_ID_INVALID as CK_RV
+        );
+    }
+
+    #[test]
+    fn open_session_never_reuses_a_handle() {
+        let manager_proxy = ManagerProxy::new();
+        let first = manager_proxy.open_session(0).unwrap();
+        let second = manager_proxy.open_session(0).unwrap();
+        assert_ne!(first, second);
+    }
+}